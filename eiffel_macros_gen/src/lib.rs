@@ -10,15 +10,31 @@
 //! implemented or may undergo significant changes in future updates.
 //!
 //! Contributions and feedback are always welcome.
+//!
+//! # Feature flags
+//!
+//! Contract generation lives behind the default `contracts` feature. Turning it
+//! off (`default-features = false`) compiles every attribute macro in this crate
+//! down to a zero-overhead pass-through that emits the annotated item unchanged,
+//! and `eiffel_loop!` down to the plain `while` loop without its checks. The
+//! manifest declares it as:
+//!
+//! ```toml
+//! [features]
+//! default = ["contracts"]
+//! contracts = []
+//! ```
 #![deny(warnings)]
 #![deny(missing_docs)]
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::{ quote, format_ident };
-use syn::{parse_macro_input, ItemFn, ReturnType, Result, FnArg, Pat, Ident};
+use syn::{parse_macro_input, ItemFn, ItemImpl, ImplItem, ReturnType, Result, FnArg, Ident, Expr, LitStr, Token, Block, Path};
 use syn::parse::{Parse, ParseStream};
-use proc_macro2::TokenTree;
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
 use syn::token::Comma;
 
 enum CheckTime {
@@ -29,40 +45,285 @@ enum CheckTime {
     BeforeAndAfter,
 }
 
+/// Controls whether generated contract checks survive into release builds.
+///
+/// By default checks are wrapped in `if cfg!(debug_assertions) { ... }` so they
+/// behave like `debug_assert!`; the `always` flag keeps them in every build.
+enum ContractGate {
+    /// Only run the checks when `debug_assertions` is enabled (the default).
+    DebugOnly,
+    /// Run the checks unconditionally, including in release builds.
+    Always,
+}
+
+impl ContractGate {
+    /// Wraps a block of checks according to the gate.
+    fn wrap(&self, checks: TokenStream2) -> TokenStream2 {
+        match self {
+            ContractGate::Always => checks,
+            ContractGate::DebugOnly => quote! {
+                if cfg!(debug_assertions) {
+                    #checks
+                }
+            },
+        }
+    }
+}
+
+/// Whether contract checking is compiled in at all.
+///
+/// Controlled by the crate's `contracts` feature, which is enabled by default.
+/// When it is turned off every macro in this crate degrades to a pass-through
+/// that emits the original item unchanged, with zero generated wrapper code.
+fn contracts_enabled() -> bool {
+    cfg!(feature = "contracts")
+}
+
 struct AttrList {
-    #[allow(dead_code)]
-    invariant_function_identifier: Ident,
-    #[allow(dead_code)]
-    rest: Vec<TokenTree>,
+    invariant: Path,
+    check_time: Option<CheckTime>,
+    message: Option<LitStr>,
+    on: Option<Ident>,
+    always: bool,
+    disable_in_release: bool,
 }
 
 impl Parse for AttrList {
     fn parse(input: ParseStream) -> Result<Self> {
-        let first_ident: Ident = input.parse()?;
-
-        if input.is_empty() {
-            return Ok(AttrList { invariant_function_identifier: first_ident, rest: vec![] });
-        }
+        let invariant: Path = input.parse()?;
 
-        let mut rest = Vec::new();
+        let mut attr = AttrList {
+            invariant,
+            check_time: None,
+            message: None,
+            on: None,
+            always: false,
+            disable_in_release: false,
+        };
 
         while !input.is_empty() {
             let _: Comma = input.parse()?;
-            let item: TokenTree = input.parse()?;
-            rest.push(item);
+
+            // Tolerate a trailing comma after the last option.
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+
+            if input.peek(Token![=]) {
+                // A named `key = "value"` option.
+                let _: Token![=] = input.parse()?;
+                let value: LitStr = input.parse()?;
+
+                match key.to_string().as_str() {
+                    "check" => {
+                        let check_time = match value.value().as_str() {
+                            "before" => CheckTime::Before,
+                            "after" => CheckTime::After,
+                            "before_and_after" => CheckTime::BeforeAndAfter,
+                            other => {
+                                return Err(syn::Error::new(
+                                    value.span(),
+                                    format!(
+                                        "unknown check time {:?}, expected \"before\", \"after\" or \"before_and_after\"",
+                                        other
+                                    ),
+                                ))
+                            }
+                        };
+                        attr.check_time = Some(check_time);
+                    }
+                    "message" => attr.message = Some(value),
+                    "on" => {
+                        syn::parse_str::<Ident>(&value.value()).map_err(|_| {
+                            syn::Error::new(
+                                value.span(),
+                                format!("`{}` is not a valid identifier", value.value()),
+                            )
+                        })?;
+                        attr.on = Some(Ident::new(&value.value(), value.span()));
+                    }
+                    other => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            format!("unknown option `{}`", other),
+                        ))
+                    }
+                }
+            } else {
+                // A bare boolean flag.
+                match key.to_string().as_str() {
+                    "always" => attr.always = true,
+                    "disable_in_release" => attr.disable_in_release = true,
+                    other => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            format!("unknown flag `{}`", other),
+                        ))
+                    }
+                }
+            }
         }
 
-        Ok(AttrList { invariant_function_identifier: first_ident, rest })
+        Ok(attr)
     }
 }
 
+/// Parsed argument list for `require`/`ensure`: one or more boolean condition
+/// expressions, comma-separated. Unlike `check_invariant`, neither
+/// macro takes named options, so a `key = value`-shaped argument is almost
+/// always a typo'd option rather than a real condition and is rejected here
+/// with a clear error instead of being parsed as an `Expr::Assign` that then
+/// fails obscurely once it reaches the generated code.
+struct ConditionList {
+    conditions: Punctuated<Expr, Comma>,
+}
+
+impl Parse for ConditionList {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let conditions = Punctuated::<Expr, Comma>::parse_terminated(input)?;
+
+        for condition in &conditions {
+            if let Expr::Assign(assign) = condition {
+                return Err(syn::Error::new_spanned(
+                    assign,
+                    "expected a boolean condition, found `key = value`; require/ensure take only conditions and have no named options",
+                ));
+            }
+        }
+
+        Ok(ConditionList { conditions })
+    }
+}
+
+/// Shared code generator behind `check_invariant`, `invariants`, `require` and
+/// `ensure`.
+///
+/// It emits a wrapper that keeps the original signature — re-emitting the
+/// visibility, attributes, generics, `async`/`unsafe` qualifiers and return
+/// type so it is a faithful stand-in — and runs `pre` before the original body
+/// and `post` afterwards. The body is run behind a call boundary (a closure, or
+/// an `async` block for `async fn`s) so an early `return` or `?` inside it
+/// returns from the body rather than the wrapper, letting `post` still run.
+/// Capturing the environment means the same shape works for methods, free
+/// functions and associated functions — including those that mention `Self`,
+/// an impl generic parameter, or destructure their parameters — without routing
+/// the call through a renamed sibling.
+struct WrappedFn<'a> {
+    attrs: &'a [syn::Attribute],
+    vis: &'a syn::Visibility,
+    sig: &'a syn::Signature,
+    body: &'a Block,
+}
+
+impl WrappedFn<'_> {
+    /// Emits the wrapper: `pre`, then the original body, then `post`. `post` may
+    /// reference the body's return value through the bound identifier `result`.
+    fn emit(&self, pre: TokenStream2, post: TokenStream2) -> TokenStream2 {
+        let attrs = self.attrs;
+        let vis = self.vis;
+        let sig = self.sig;
+        let body = self.body;
+
+        let run_body = if sig.asyncness.is_some() {
+            quote! { (async #body).await }
+        } else {
+            // The return type is annotated onto the closure so `?` and early
+            // `return` inside the body keep type-checking against it.
+            let ret = match &sig.output {
+                ReturnType::Default => quote! {},
+                ReturnType::Type(arrow, ty) => quote! { #arrow #ty },
+            };
+            quote! { (|| #ret #body)() }
+        };
+
+        // Without `post` the body is the wrapper's trailing expression; with it,
+        // the return value is bound as `result` so `post` can refer to it.
+        let wrapper_body = if post.is_empty() {
+            quote! {
+                #pre
+                #run_body
+            }
+        } else {
+            quote! {
+                #pre
+                let result = #run_body;
+                #post
+                result
+            }
+        };
+
+        quote! {
+            #(#attrs)*
+            #vis #sig { #wrapper_body }
+        }
+    }
+}
+
+/// Builds the before/after invariant checks shared by `check_invariant` and
+/// `invariants`.
+///
+/// The invariant is called as `receiver.invariant()` when it is a bare
+/// identifier, or as `invariant(&receiver)` when it is a multi-segment path,
+/// panicking with `message` (or the default entry/exit wording) on violation.
+fn invariant_checks(
+    invariant: &Path,
+    receiver: &TokenStream2,
+    check_time: &CheckTime,
+    message: &Option<LitStr>,
+    gate: &ContractGate,
+) -> (TokenStream2, TokenStream2) {
+    // The invariant is either a method on the receiver (a single identifier,
+    // e.g. `state.is_valid()`) or a free predicate path taking a reference to
+    // the receiver (e.g. `my_mod::is_valid(&state)`).
+    let invariant_call = match invariant.get_ident() {
+        Some(method) => quote! { #receiver.#method() },
+        None => quote! { #invariant(&#receiver) },
+    };
+
+    // Either the user's custom message or the default entry/exit wording.
+    let before_panic = match message {
+        Some(message) => quote! { panic!("{}", #message); },
+        None => quote! { panic!("Invariant {} failed on entry", stringify!(#invariant)); },
+    };
+    let after_panic = match message {
+        Some(message) => quote! { panic!("{}", #message); },
+        None => quote! { panic!("Invariant {} failed on exit", stringify!(#invariant)); },
+    };
+
+    let before = match check_time {
+        CheckTime::Before | CheckTime::BeforeAndAfter => gate.wrap(quote! {
+            if !(#invariant_call) {
+                #before_panic
+            }
+        }),
+        _ => quote! {},
+    };
+
+    let after = match check_time {
+        CheckTime::After | CheckTime::BeforeAndAfter => gate.wrap(quote! {
+            if !(#invariant_call) {
+                #after_panic
+            }
+        }),
+        _ => quote! {},
+    };
+
+    (before, after)
+}
+
 /// `check_invariant` is a procedural macro that checks if a given invariant holds true before and after a method call.
 /// If the invariant does not hold, the macro will cause the program to panic with a specified message.
 /// 
 /// # Arguments
 /// 
-/// * `invariant`: A method that returns a boolean. This is the invariant that needs to be checked.
-/// * `check_time`: An optional string literal that specifies when the invariant should be checked. The possible values are: "before", "after", "before_and_after".
+/// * `invariant`: The invariant to check. A single identifier is treated as a method on the receiver (`receiver.invariant()`); a multi-segment path is treated as a free predicate taking a reference (`invariant(&receiver)`).
+/// * `on = "..."`: An optional parameter name identifying the receiver for free or associated functions. Defaults to the `self` receiver of a method.
+/// * `check = "..."`: An optional named option that specifies when the invariant should be checked. The possible values are: "before", "after", "before_and_after".
+/// * `message = "..."`: An optional custom string used as the panic message instead of the default wording.
+/// * `always`: A bare flag that keeps the checks in release builds instead of gating them behind `debug_assertions`.
+/// * `disable_in_release`: A bare flag that keeps the default `debug_assertions` gate, so the checks do not run in release builds; it takes precedence over `always`.
 /// 
 /// # Example
 ///
@@ -87,151 +348,542 @@ impl Parse for AttrList {
 ///     }
 ///
 ///     // Only check the invariant before the method call
-///     #[check_invariant(my_invariant, "before")]
+///     #[check_invariant(my_invariant, check = "before")]
 ///     fn my_other_method(&self) {
 ///         // Method body
 ///         println!("Method body {:?}", self.a);
 ///     }
+/// }
+/// ```
 ///
-///     // Only check the invariant before the method call
-///     #[check_invariant(my_invariant, "before")]
-///     fn my_other_method(&self) {
-///         // Method body
-///         println!("Method body {:?}", self.a);
-///     }
+/// The `on` option guards a function that takes its receiver as a named
+/// parameter instead of `self`, including associated functions inside an `impl`:
 ///
-/// }       
 /// ```
+/// use eiffel_macros_gen::check_invariant;
 ///
-/// # Test
+/// struct State { value: i32 }
 ///
-/// ```
-/// #[cfg(test)]
-/// mod tests {
-///     use super::*;
-///
-///     #[test]
-///     fn test_my_method() {
-///         let my_class = MyClass;
-///         my_class.my_method(); // This should not panic as the invariant is true
+/// impl State {
+///     fn is_valid(&self) -> bool {
+///         self.value >= 0
+///     }
+///
+///     #[check_invariant(is_valid, on = "state")]
+///     fn bump(state: &mut State, delta: i32) {
+///         state.value += delta;
 ///     }
 /// }
+///
+/// let mut state = State { value: 1 };
+/// State::bump(&mut state, 4);
+/// assert_eq!(state.value, 5);
 /// ```
 #[proc_macro_attribute]
 pub fn check_invariant(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // let invariant_name = parse_macro_input!(attr as Ident);
-    // let check_time = CheckTime::BeforeAndAfter;
-    let mut check_time = None;
-    
+    // With the `contracts` feature off the macro is a pure pass-through.
+    if !contracts_enabled() {
+        return item;
+    }
+
     let attr = parse_macro_input!(attr as AttrList);
-    let invariant_name = attr.invariant_function_identifier;
+    let invariant = attr.invariant;
+    let check_time = attr.check_time.unwrap_or(CheckTime::BeforeAndAfter);
+    let message = attr.message;
 
-    for item in attr.rest.into_iter() {
-        match item {
-            TokenTree::Literal(literal) => {
-                let msg = literal.to_string();
-                match msg.as_str() {
-                    "\"before\"" => check_time = Some(CheckTime::Before),
-                    "\"after\"" => check_time = Some(CheckTime::After),
-                    "\"before_and_after\"" => check_time = Some(CheckTime::BeforeAndAfter),
-                    _ => panic!("Invalid check time: {}, expected one of: \"before\", \"after\", \"before_and_after\"", msg)
-                }
+    // `always` keeps checks in release builds; an explicit `disable_in_release`
+    // wins any conflict and forces the default `debug_assertions` gate.
+    let gate = if attr.always && !attr.disable_in_release {
+        ContractGate::Always
+    } else {
+        ContractGate::DebugOnly
+    };
+
+    let input_fn = parse_macro_input!(item as ItemFn);
+
+    // Locate the receiver the invariant is checked against: the parameter named
+    // by `on`, otherwise the `self` receiver of a method.
+    let has_self = matches!(input_fn.sig.inputs.first(), Some(FnArg::Receiver(_)));
+    let receiver = match &attr.on {
+        Some(name) => quote! { #name },
+        None => {
+            if !has_self {
+                return syn::Error::new_spanned(
+                    &input_fn.sig,
+                    "check_invariant needs a `self` receiver or an `on = \"param\"` option naming the receiver",
+                )
+                .to_compile_error()
+                .into();
             }
-            _ => {}
+            quote! { self }
         }
+    };
+
+    let (pre, post) = invariant_checks(&invariant, &receiver, &check_time, &message, &gate);
+
+    let output = WrappedFn {
+        attrs: &input_fn.attrs,
+        vis: &input_fn.vis,
+        sig: &input_fn.sig,
+        body: &input_fn.block,
     }
+    .emit(pre, post);
 
-    let check_time = check_time.unwrap_or(CheckTime::BeforeAndAfter);
+    output.into()
+}
 
-    // Extract the name, arguments, and return type of the input function
-    let input_fn = parse_macro_input!(item as ItemFn);
-    let input_fn_name = &input_fn.sig.ident;
-    let input_fn_body = &input_fn.block;
-
-    let args = &input_fn.sig.inputs;
-    let arg_names: Vec<Ident> = args
-        .iter()
-        .filter_map(|arg| {
-            if let FnArg::Typed(pat) = arg {
-                if let Pat::Ident(pat_ident) = &*pat.pat {
-                    return Some(pat_ident.ident.clone());
+
+/// Rewrites Eiffel-style `old(expr)` calls inside a postcondition expression
+/// into references to pre-computed `__old_N` bindings.
+///
+/// Each distinct inner expression is captured once (in source order) so that a
+/// single `let __old_N = (expr).clone();` can be emitted before the wrapped
+/// body runs, giving `ensure` access to the value an expression had on entry.
+struct OldRewriter {
+    captured: Vec<Expr>,
+}
+
+impl VisitMut for OldRewriter {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // Recurse first so that nested `old(...)` calls are handled too.
+        visit_mut::visit_expr_mut(self, expr);
+
+        if let Expr::Call(call) = expr {
+            if let Expr::Path(path) = &*call.func {
+                if path.path.is_ident("old") && call.args.len() == 1 {
+                    let inner = call.args.first().unwrap().clone();
+                    let key = quote! { #inner }.to_string();
+                    let index = match self
+                        .captured
+                        .iter()
+                        .position(|e| quote! { #e }.to_string() == key)
+                    {
+                        Some(index) => index,
+                        None => {
+                            self.captured.push(inner);
+                            self.captured.len() - 1
+                        }
+                    };
+                    let ident = format_ident!("__old_{}", index);
+                    *expr = syn::parse_quote! { #ident };
                 }
             }
-            None
-        })
-        .collect();
-    
-    let _self_arg = match args.first() {
-        Some(FnArg::Receiver(receiver)) => receiver,
-        _ => panic!("The input function must have a self argument"),
+        }
+    }
+}
+
+/// `require` declares one or more preconditions that must hold before a method's
+/// body runs. Each argument is a boolean expression evaluated on entry; if any
+/// of them is false the method panics with `Precondition failed`.
+///
+/// Like [`check_invariant`](macro@check_invariant) it wraps the original body
+/// inline behind a call boundary instead of delegating to a renamed sibling, so
+/// `require`, `ensure` and `check_invariant` stack freely on the same function
+/// without colliding.
+///
+/// # Example
+///
+/// ```
+/// use eiffel_macros_gen::require;
+///
+/// struct Account { balance: i64 }
+///
+/// impl Account {
+///     #[require(amount > 0, amount <= self.balance)]
+///     fn withdraw(&mut self, amount: i64) {
+///         self.balance -= amount;
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn require(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // With the `contracts` feature off the macro is a pure pass-through.
+    if !contracts_enabled() {
+        return item;
+    }
+
+    let preconditions = parse_macro_input!(attr as ConditionList).conditions;
+
+    let input_fn = parse_macro_input!(item as ItemFn);
+
+    let checks = preconditions.iter().map(|expr| quote! {
+        if !(#expr) {
+            panic!("Precondition failed: {}", stringify!(#expr));
+        }
+    });
+    // Contract checks behave like `debug_assert!`: they only run when
+    // `debug_assertions` is enabled.
+    let pre = quote! {
+        if cfg!(debug_assertions) {
+            #(#checks)*
+        }
     };
 
-    let return_type = match &input_fn.sig.output {
-        ReturnType::Default => None,
-        ReturnType::Type(_, ty) => Some(quote! { #ty }),
+    let output = WrappedFn {
+        attrs: &input_fn.attrs,
+        vis: &input_fn.vis,
+        sig: &input_fn.sig,
+        body: &input_fn.block,
+    }
+    .emit(pre, quote! {});
+
+    output.into()
+}
+
+/// `ensure` declares one or more postconditions that must hold after a method's
+/// body runs. Each argument is a boolean expression evaluated on exit; if any of
+/// them is false the method panics with `Postcondition failed`.
+///
+/// The return value is available to the expressions through the bound identifier
+/// `result`. Postconditions may also refer to the value an expression had *on
+/// entry* via Eiffel's `old(expr)`: every distinct inner expression is
+/// `clone`d into a hidden binding before the body runs and substituted into the
+/// check. Expressions wrapped in `old(...)` must therefore be [`Clone`]; if they
+/// are not, the generated `(expr).clone()` produces a compile error pointing at
+/// the offending expression.
+///
+/// # Example
+///
+/// ```
+/// use eiffel_macros_gen::ensure;
+///
+/// struct Counter { value: i64 }
+///
+/// impl Counter {
+///     #[ensure(self.value == old(self.value) + 1, result == self.value)]
+///     fn increment(&mut self) -> i64 {
+///         self.value += 1;
+///         self.value
+///     }
+/// }
+/// ```
+///
+/// `require`, `ensure` and `check_invariant` stack on a single method because
+/// each wraps the body inline behind its own call boundary instead of
+/// delegating to a renamed sibling:
+///
+/// ```
+/// use eiffel_macros_gen::{require, ensure};
+///
+/// struct Account { balance: i64 }
+///
+/// impl Account {
+///     #[require(amount > 0, amount <= self.balance)]
+///     #[ensure(self.balance == old(self.balance) - amount)]
+///     fn withdraw(&mut self, amount: i64) {
+///         self.balance -= amount;
+///     }
+/// }
+///
+/// let mut account = Account { balance: 100 };
+/// account.withdraw(30);
+/// assert_eq!(account.balance, 70);
+/// ```
+#[proc_macro_attribute]
+pub fn ensure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // With the `contracts` feature off the macro is a pure pass-through.
+    if !contracts_enabled() {
+        return item;
+    }
+
+    let mut postconditions = parse_macro_input!(attr as ConditionList).conditions;
+
+    // Capture every `old(...)` expression so it can be cloned before the body
+    // runs, rewriting the check to reference the captured `__old_N` binding.
+    let mut rewriter = OldRewriter { captured: Vec::new() };
+    for expr in postconditions.iter_mut() {
+        rewriter.visit_expr_mut(expr);
+    }
+    let old_bindings = rewriter.captured.iter().enumerate().map(|(index, expr)| {
+        let ident = format_ident!("__old_{}", index);
+        quote::quote_spanned! {syn::spanned::Spanned::span(expr)=>
+            let #ident = (#expr).clone();
+        }
+    });
+
+    let input_fn = parse_macro_input!(item as ItemFn);
+
+    let checks = postconditions.iter().map(|expr| quote! {
+        if !(#expr) {
+            panic!("Postcondition failed: {}", stringify!(#expr));
+        }
+    });
+    // Contract checks behave like `debug_assert!`: they only run when
+    // `debug_assertions` is enabled.
+    let post = quote! {
+        if cfg!(debug_assertions) {
+            #(#checks)*
+        }
     };
 
-    // Rename the original function
-    let fn_without_invariant = format_ident!("{}_no_invariant", input_fn_name);
-    
-    let wrapped_function = match &return_type {
-        None => quote! {
-            fn #fn_without_invariant(#args) { 
-                #input_fn_body
+    // The captured `old(...)` values are cloned before the body runs.
+    let pre = quote! { #(#old_bindings)* };
+
+    let output = WrappedFn {
+        attrs: &input_fn.attrs,
+        vis: &input_fn.vis,
+        sig: &input_fn.sig,
+        body: &input_fn.block,
+    }
+    .emit(pre, post);
+
+    output.into()
+}
+
+
+/// Parsed form of an `eiffel_loop!` invocation: the optional `invariant` and
+/// `variant` clauses followed by a `while` loop.
+struct EiffelLoop {
+    invariant: Option<Expr>,
+    variant: Option<Expr>,
+    condition: Expr,
+    body: Block,
+}
+
+impl Parse for EiffelLoop {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut invariant = None;
+        let mut variant = None;
+
+        // Both clauses are optional; they precede the loop and are written as
+        // `invariant: <expr>;` / `variant: <expr>;`.
+        while input.peek(Ident) {
+            let key: Ident = input.parse()?;
+            let _: Token![:] = input.parse()?;
+            let expr: Expr = input.parse()?;
+            let _: Token![;] = input.parse()?;
+
+            match key.to_string().as_str() {
+                "invariant" => invariant = Some(expr),
+                "variant" => variant = Some(expr),
+                other => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown `eiffel_loop!` clause `{}`, expected `invariant` or `variant`",
+                            other
+                        ),
+                    ))
+                }
             }
-        },
-        Some(return_type) => quote! {
-            fn #fn_without_invariant(#args) -> #return_type { 
-                #input_fn_body
+        }
+
+        let _: Token![while] = input.parse()?;
+        let condition = input.call(Expr::parse_without_eager_brace)?;
+        let body: Block = input.parse()?;
+
+        Ok(EiffelLoop { invariant, variant, condition, body })
+    }
+}
+
+/// `eiffel_loop!` expands a `while` loop annotated with an Eiffel-style loop
+/// invariant and loop variant into a plain loop that proves correctness and
+/// termination at run time.
+///
+/// The invariant is asserted before the first iteration and again after every
+/// iteration. The variant is a bounded-below integer measure: after each
+/// iteration it is asserted to be non-negative and to have strictly decreased
+/// relative to the previous iteration, panicking with `Loop variant did not
+/// decrease (possible non-termination)` otherwise. Both clauses are optional and
+/// independent.
+///
+/// # Example
+///
+/// ```
+/// use eiffel_macros_gen::eiffel_loop;
+///
+/// let mut i = 5;
+/// let mut sum = 0;
+/// eiffel_loop! {
+///     invariant: i >= 0;
+///     variant: i;
+///     while i > 0 {
+///         sum += i;
+///         i -= 1;
+///     }
+/// }
+/// assert_eq!(sum, 15);
+/// ```
+#[proc_macro]
+pub fn eiffel_loop(input: TokenStream) -> TokenStream {
+    let EiffelLoop { invariant, variant, condition, body } =
+        parse_macro_input!(input as EiffelLoop);
+
+    // With the `contracts` feature off, emit the plain loop with no checks.
+    if !contracts_enabled() {
+        let body_stmts = &body.stmts;
+        return quote! {
+            while #condition {
+                #(#body_stmts)*
             }
         }
-    };
+        .into();
+    }
 
-    let call_invariant_before = match check_time {
-        CheckTime::Before | CheckTime::BeforeAndAfter => quote! {
-            if !self.#invariant_name() {
-                panic!("Invariant {} failed on entry", stringify!(#invariant_name));
+    let invariant_before = match &invariant {
+        Some(invariant) => quote! {
+            if cfg!(debug_assertions) {
+                assert!(#invariant, "Loop invariant failed before the first iteration");
             }
         },
-        _ => quote! {},
+        None => quote! {},
     };
-
-    let call_invariant_after = match check_time {
-        CheckTime::After | CheckTime::BeforeAndAfter => quote! {
-            if !self.#invariant_name() {
-                panic!("Invariant {} failed on exit", stringify!(#invariant_name));
+    let invariant_after = match &invariant {
+        Some(invariant) => quote! {
+            if cfg!(debug_assertions) {
+                assert!(#invariant, "Loop invariant failed after an iteration");
             }
         },
-        _ => quote! {},
+        None => quote! {},
     };
 
-    let call_wrapped = quote! {
-        self.#fn_without_invariant( #(#arg_names),*)
+    let variant_prev_decl = match &variant {
+        Some(_) => quote! { let mut __eiffel_variant_prev = None; },
+        None => quote! {},
     };
-
-    let invariant_checked_function = match return_type {
-        None => quote! {
-            fn #input_fn_name(#args) { 
-                #call_invariant_before
-                #call_wrapped;
-                #call_invariant_after
+    let variant_check = match &variant {
+        Some(variant) => quote! {
+            if cfg!(debug_assertions) {
+                let __eiffel_variant_now = #variant;
+                assert!(
+                    __eiffel_variant_now >= 0,
+                    "Loop variant is negative (it must stay bounded below by zero)"
+                );
+                if let Some(__eiffel_variant_prev_value) = __eiffel_variant_prev {
+                    assert!(
+                        __eiffel_variant_now < __eiffel_variant_prev_value,
+                        "Loop variant did not decrease (possible non-termination)"
+                    );
+                }
+                __eiffel_variant_prev = Some(__eiffel_variant_now);
             }
         },
-        Some(return_type) => quote! {
-            fn #input_fn_name(#args) -> #return_type {
-                #call_invariant_before
-                let result = #call_wrapped;
-                #call_invariant_after
-                result
+        None => quote! {},
+    };
+
+    let body_stmts = &body.stmts;
+
+    let output = quote! {
+        {
+            #variant_prev_decl
+            #invariant_before
+            while #condition {
+                #(#body_stmts)*
+                #invariant_after
+                #variant_check
             }
         }
     };
 
-    // Generate the wrapper code
+    output.into()
+}
+
+/// `invariants` is an `impl`-block attribute that applies the same before/after
+/// invariant checking as [`check_invariant`](macro@check_invariant) to *every*
+/// `self` method in the block, giving a single Eiffel-style class invariant
+/// instead of annotating each routine by hand.
+///
+/// The named invariant method itself is left untouched, as is any method tagged
+/// with `#[no_invariant]` (the opt-out attribute is stripped during expansion).
+/// Associated functions without a `self` receiver are passed through unchanged.
+///
+/// # Example
+///
+/// ```
+/// use eiffel_macros_gen::invariants;
+///
+/// struct Account { balance: i64 }
+///
+/// #[invariants(is_valid)]
+/// impl Account {
+///     fn is_valid(&self) -> bool {
+///         self.balance >= 0
+///     }
+///
+///     fn deposit(&mut self, amount: i64) {
+///         self.balance += amount;
+///     }
+///
+///     #[no_invariant]
+///     fn force_overdraw(&mut self) {
+///         self.balance = -1;
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn invariants(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // With the `contracts` feature off the macro is a pure pass-through.
+    if !contracts_enabled() {
+        return item;
+    }
+
+    let invariant_name = parse_macro_input!(attr as Ident);
+    let mut input_impl = parse_macro_input!(item as ItemImpl);
+
+    // The class invariant is a single method on `self`.
+    let invariant_path: Path = invariant_name.clone().into();
+    let receiver = quote! { self };
+
+    let items = std::mem::take(&mut input_impl.items);
+    let mut new_items: Vec<ImplItem> = Vec::new();
+
+    for item in items.into_iter() {
+        match item {
+            ImplItem::Fn(mut method) => {
+                // Never wrap the invariant predicate itself.
+                if method.sig.ident == invariant_name {
+                    new_items.push(ImplItem::Fn(method));
+                    continue;
+                }
+
+                // Honour the `#[no_invariant]` opt-out, removing the marker so it
+                // does not leak into the emitted method.
+                if let Some(position) = method
+                    .attrs
+                    .iter()
+                    .position(|attr| attr.path().is_ident("no_invariant"))
+                {
+                    method.attrs.remove(position);
+                    new_items.push(ImplItem::Fn(method));
+                    continue;
+                }
+
+                // Only methods with a `self` receiver can be guarded.
+                if !matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+                    new_items.push(ImplItem::Fn(method));
+                    continue;
+                }
+
+                let (pre, post) = invariant_checks(
+                    &invariant_path,
+                    &receiver,
+                    &CheckTime::BeforeAndAfter,
+                    &None,
+                    &ContractGate::DebugOnly,
+                );
+
+                let wrapped = WrappedFn {
+                    attrs: &method.attrs,
+                    vis: &method.vis,
+                    sig: &method.sig,
+                    body: &method.block,
+                }
+                .emit(pre, post);
+
+                // Re-parse the generated wrapper as an impl item so it can take
+                // the place of the original method.
+                let parsed: ItemImpl = syn::parse2(quote! { impl __EiffelInvariants { #wrapped } })
+                    .expect("generated invariant wrapper should parse");
+                new_items.extend(parsed.items);
+            }
+            other => new_items.push(other),
+        }
+    }
+
+    input_impl.items = new_items;
+
     let output = quote! {
-        #wrapped_function
-    
-        #invariant_checked_function
+        #input_impl
     };
 
     output.into()
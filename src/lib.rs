@@ -3,9 +3,6 @@
 #![deny(warnings)]
 #![deny(missing_docs)]
 
-// Re-export macros from eiffel-macros submodule
-pub use eiffel_macros::*;
-
 // Re-export macros from eiffel-gen submodule
 pub use eiffel_macros_gen::*;
 
@@ -35,14 +32,14 @@ mod tests {
       // println!("Method body {:?}", self.a);
     }
 
-    #[check_invariant(my_invariant, "before")]
+    #[check_invariant(my_invariant, check = "before")]
     fn my_method_before_only(&mut self, value_to_add: i32) {
       // Method body
       self.a += value_to_add;
       // println!("Method body {:?}", self.a);
     }
 
-    #[check_invariant(my_invariant, "after")]
+    #[check_invariant(my_invariant, check = "after")]
     fn my_method_after_only(&mut self, value_to_add: i32) {
       // Method body
       self.a += value_to_add;
@@ -95,4 +92,141 @@ mod tests {
 
     assert_eq!(my_class.a, 1);
   }
+
+  #[test]
+  fn test_eiffel_loop_runs_to_completion() {
+    use eiffel_macros_gen::eiffel_loop;
+
+    let mut i = 5;
+    let mut sum = 0;
+
+    eiffel_loop! {
+      invariant: i >= 0;
+      variant: i;
+      while i > 0 {
+        sum += i;
+        i -= 1;
+      }
+    }
+
+    assert_eq!(sum, 15);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_invariants_impl_wide_guard() {
+    use eiffel_macros_gen::invariants;
+
+    struct Balance {
+      amount: i32,
+    }
+
+    #[invariants(is_valid)]
+    impl Balance {
+      fn is_valid(&self) -> bool {
+        self.amount >= 0
+      }
+
+      fn subtract(&mut self, value: i32) {
+        self.amount -= value;
+      }
+    }
+
+    let mut balance = Balance { amount: 1 };
+
+    // Takes the amount negative, violating the class invariant on exit.
+    balance.subtract(2);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_check_invariant_on_named_receiver() {
+    use eiffel_macros_gen::check_invariant;
+
+    struct State {
+      value: i32,
+    }
+
+    impl State {
+      fn is_valid(&self) -> bool {
+        self.value >= 0
+      }
+    }
+
+    // A free function guarded through the `on` parameter rather than `self`.
+    #[check_invariant(is_valid, on = "state")]
+    fn mutate(state: &mut State, delta: i32) {
+      state.value += delta;
+    }
+
+    let mut state = State { value: 1 };
+    mutate(&mut state, -5);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_check_invariant_on_associated_function() {
+    use eiffel_macros_gen::check_invariant;
+
+    struct State {
+      value: i32,
+    }
+
+    impl State {
+      fn is_valid(&self) -> bool {
+        self.value >= 0
+      }
+
+      // An associated function (no `self`) guarded through `on`; the wrapper is
+      // emitted in place inside the `impl`, not as a free call.
+      #[check_invariant(is_valid, on = "state")]
+      fn mutate(state: &mut State, delta: i32) {
+        state.value += delta;
+      }
+    }
+
+    let mut state = State { value: 1 };
+    State::mutate(&mut state, -5);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_eiffel_loop_variant_not_decreasing_panics() {
+    use eiffel_macros_gen::eiffel_loop;
+
+    let mut i = 1;
+
+    // The variant never decreases, so the termination check must fire.
+    eiffel_loop! {
+      variant: i;
+      while i > 0 {
+        i += 1;
+      }
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_require_and_ensure_stack_on_one_method() {
+    use eiffel_macros_gen::{ensure, require};
+
+    struct Account {
+      balance: i64,
+    }
+
+    impl Account {
+      // Both attributes apply to the same method; each wraps the body inline
+      // behind its own call boundary so they compose instead of colliding.
+      #[require(amount > 0, amount <= self.balance)]
+      #[ensure(self.balance == old(self.balance) - amount)]
+      fn withdraw(&mut self, amount: i64) {
+        self.balance -= amount;
+      }
+    }
+
+    let mut account = Account { balance: 50 };
+
+    // Overdraw violates the precondition `amount <= self.balance`.
+    account.withdraw(100);
+  }
 }
\ No newline at end of file